@@ -1,16 +1,29 @@
-pub fn only_one_bit_set_to_one(bitboard: u32) -> bool {
+/// Width of one row in the bitboard.
+/// It resembles a full chess board, so smaller fields only use
+/// the bottom right part of it and pad each row to this width.
+pub const BITBOARD_WIDTH: usize = 8;
+
+/// Returns the bit position of the given field on a NxN field.
+/// Row 0 / column 0 is the top left field, which maps to the highest used bit.
+/// n must not be larger than `BITBOARD_WIDTH`.
+pub fn position(row: usize, column: usize, n: usize) -> usize {
+    debug_assert!(n <= BITBOARD_WIDTH);
+    (n - row - 1) * BITBOARD_WIDTH + (n - column - 1)
+}
+
+pub fn only_one_bit_set_to_one(bitboard: u64) -> bool {
     bitboard != 0 && (bitboard & (bitboard - 1)) == 0
 }
 
-pub fn only_different(data1: u32, data2: u32) -> u32 {
+pub fn only_different(data1: u64, data2: u64) -> u64 {
     return data1 ^ data2;
 }
 
-pub fn set_bit(data: u32, bit_position: usize) -> u32 {
+pub fn set_bit(data: u64, bit_position: usize) -> u64 {
     data | (1 << bit_position)
 }
 
-pub fn get(data: u32, pos: usize) -> bool {
+pub fn get(data: u64, pos: usize) -> bool {
     // Erzeuge eine Maske, um nur das Bit an der pos-ten Position zu isolieren
     let mask = 1 << pos;
 
@@ -3,12 +3,27 @@ use esp_idf_hal::{
     peripheral::Peripheral,
 };
 
-use crate::bitboard::set_bit;
+use crate::{
+    bitboard::{position, set_bit, BITBOARD_WIDTH},
+    mapping::SquareMapping,
+};
 
 pub struct Board<'a, const N: usize> {
     column_pins: [PinDriver<'a, AnyIOPin, esp_idf_hal::gpio::Output>; N],
     row_pins: [PinDriver<'a, AnyIOPin, esp_idf_hal::gpio::Input>; N],
 
+    /// Describes how the sensor matrix is routed compared to the logical field.
+    mapping: SquareMapping,
+
+    /// How many ticks in a row a field has to read the same new value
+    /// before the change is taken over into `field`.
+    /// Reed switches chatter while a magnet approaches, which would otherwise
+    /// look like a piece got placed and removed again.
+    debounce_ticks: u8,
+
+    /// Counts for each field how many ticks in a row the read value differs from `field`.
+    unstable_ticks: [[u8; N]; N],
+
     pub field: [[bool; N]; N],
 }
 
@@ -16,10 +31,18 @@ impl<'a, const N: usize> Board<'a, N> {
     pub fn new(
         column_pins: [impl Peripheral<P = AnyIOPin> + 'a; N],
         row_pins: [impl Peripheral<P = AnyIOPin> + 'a; N],
+        mapping: SquareMapping,
+        debounce_ticks: u8,
     ) -> Self {
+        // Each row of the bitboard has only BITBOARD_WIDTH bits.
+        assert!(N <= BITBOARD_WIDTH);
+
         Board {
             column_pins: column_pins.map(|pin| PinDriver::output(pin).unwrap()),
             row_pins: row_pins.map(|pin| PinDriver::input(pin).unwrap()),
+            mapping,
+            debounce_ticks,
+            unstable_ticks: [[0; N]; N],
 
             field: [[false; N]; N],
         }
@@ -29,6 +52,10 @@ impl<'a, const N: usize> Board<'a, N> {
         N
     }
 
+    pub fn mapping(&self) -> SquareMapping {
+        self.mapping
+    }
+
     pub fn setup(&mut self) {
         // Set up the pullup.
         for pin in &mut self.row_pins {
@@ -41,22 +68,43 @@ impl<'a, const N: usize> Board<'a, N> {
         }
     }
 
-    pub fn tick(&mut self) {
+    /// Reads all fields.
+    /// Returns true if any field read differs from the current `field`,
+    /// even if the change is not taken over yet because of the debouncing.
+    pub fn tick(&mut self) -> bool {
+        let mut changed = false;
+
         // Check each field
         for (col, col_pin) in &mut self.column_pins.iter_mut().enumerate() {
             col_pin.set_low().unwrap();
 
             for (row, row_pin) in &mut self.row_pins.iter().enumerate() {
-                self.field[row][col] = row_pin.get_level() == Level::Low;
+                let (field_row, field_col) = self.mapping.to_logical((row, col), N);
+                let is_set = row_pin.get_level() == Level::Low;
+
+                let unstable_ticks = &mut self.unstable_ticks[field_row][field_col];
+                if is_set == self.field[field_row][field_col] {
+                    *unstable_ticks = 0;
+                    continue;
+                }
+
+                changed = true;
+                *unstable_ticks += 1;
+                if *unstable_ticks >= self.debounce_ticks {
+                    self.field[field_row][field_col] = is_set;
+                    *unstable_ticks = 0;
+                }
             }
 
             col_pin.set_high().unwrap();
         }
+
+        changed
     }
 
     /// Returns the current state as bitboard representation.
     /// For compatibility with the chess board later it resembles to
-    /// a full u32 bitboard, but uses only the bottom right part of it.
+    /// a full u64 bitboard, but uses only the bottom right part of it.
     ///
     /// ```
     /// msb
@@ -69,8 +117,8 @@ impl<'a, const N: usize> Board<'a, N> {
     /// 00000111
     /// 00000111 lsb
     /// ```
-    pub fn bitboard(&self) -> u32 {
-        let mut bit_board: u32 = 0;
+    pub fn bitboard(&self) -> u64 {
+        let mut bit_board: u64 = 0;
 
         for (row, columns) in self.field.iter().enumerate() {
             for (column, is_set) in columns.iter().enumerate() {
@@ -78,9 +126,7 @@ impl<'a, const N: usize> Board<'a, N> {
                     continue;
                 }
 
-                //                                                    + Padding to the bigger u32 chess board
-                let pos = (N - row - 1) * N + (N - column - 1) + (N - row - 1) * (8 - N);
-                bit_board = set_bit(bit_board, pos);
+                bit_board = set_bit(bit_board, position(row, column, N));
             }
         }
 
@@ -0,0 +1,55 @@
+use std::{thread::sleep, time::Duration};
+
+use esp_idf_hal::{
+    gpio::OutputPin,
+    ledc::{config::TimerConfig, LedcDriver, LedcTimerDriver, CHANNEL0, TIMER0},
+    peripheral::Peripheral,
+    units::Hertz,
+};
+use esp_idf_sys::EspError;
+
+/// Short high click for a successful move.
+const OK_FREQUENCY: Hertz = Hertz(2000);
+const OK_DURATION: Duration = Duration::from_millis(30);
+
+/// Longer low buzz for a rejected change of the board.
+const ERROR_FREQUENCY: Hertz = Hertz(400);
+const ERROR_DURATION: Duration = Duration::from_millis(200);
+
+/// Drives a piezo buzzer using a PWM signal.
+pub struct Buzzer<'a> {
+    timer: LedcTimerDriver<'a, TIMER0>,
+    driver: LedcDriver<'a>,
+}
+
+impl<'a> Buzzer<'a> {
+    pub fn new(
+        timer: impl Peripheral<P = TIMER0> + 'a,
+        channel: impl Peripheral<P = CHANNEL0> + 'a,
+        pin: impl Peripheral<P = impl OutputPin> + 'a,
+    ) -> Result<Self, EspError> {
+        let timer = LedcTimerDriver::new(timer, &TimerConfig::new().frequency(OK_FREQUENCY))?;
+        let driver = LedcDriver::new(channel, &timer, pin)?;
+
+        Ok(Buzzer { timer, driver })
+    }
+
+    pub fn beep_ok(&mut self) -> Result<(), EspError> {
+        self.beep(OK_FREQUENCY, OK_DURATION)
+    }
+
+    pub fn beep_error(&mut self) -> Result<(), EspError> {
+        self.beep(ERROR_FREQUENCY, ERROR_DURATION)
+    }
+
+    /// Plays the given tone.
+    /// Note that it blocks until the tone is finished.
+    fn beep(&mut self, frequency: Hertz, duration: Duration) -> Result<(), EspError> {
+        self.timer.set_frequency(frequency)?;
+
+        // A 50% duty cycle is the loudest for a piezo.
+        self.driver.set_duty(self.driver.get_max_duty() / 2)?;
+        sleep(duration);
+        self.driver.set_duty(0)
+    }
+}
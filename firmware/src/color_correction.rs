@@ -0,0 +1,37 @@
+use smart_leds::RGB8;
+
+/// Corrects the colors before they are sent to the LEDs.
+/// The perceived brightness of the LEDs is not linear, so without a gamma
+/// curve dim colors look washed out.
+/// Also many strips have a too bright blue channel, which can be
+/// compensated by the per channel factors.
+pub struct ColorCorrection {
+    /// Maps a channel value to the brightness scaled and gamma corrected value.
+    lut: [u8; 256],
+
+    /// Correction factors for red, green and blue respectively.
+    channels: [f32; 3],
+}
+
+impl ColorCorrection {
+    /// `brightness` from 0.0 to 1.0 is applied before the gamma curve.
+    pub fn new(gamma: f32, brightness: f32, channels: [f32; 3]) -> Self {
+        let brightness = brightness.clamp(0.0, 1.0);
+
+        let mut lut = [0; 256];
+        for (value, corrected) in lut.iter_mut().enumerate() {
+            let scaled = value as f32 / 255.0 * brightness;
+            *corrected = (scaled.powf(gamma) * 255.0).round() as u8;
+        }
+
+        ColorCorrection { lut, channels }
+    }
+
+    pub fn scale(&self, color: RGB8) -> RGB8 {
+        RGB8 {
+            r: (self.lut[color.r as usize] as f32 * self.channels[0]) as u8,
+            g: (self.lut[color.g as usize] as f32 * self.channels[1]) as u8,
+            b: (self.lut[color.b as usize] as f32 * self.channels[2]) as u8,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use smart_leds::RGB8;
+
+/// Defines all colors used to show the game state on the LEDs.
+/// The values are perceived brightness, as they get gamma corrected
+/// by `ColorCorrection` before they are sent to the LEDs.
+#[derive(Clone, Copy)]
+pub struct ColorTheme {
+    /// The pieces of each player respectively.
+    pub players: [RGB8; 2],
+
+    /// The pieces of each player respectively if the other player has won.
+    pub players_lost: [RGB8; 2],
+
+    /// A field is occupied although it should be free.
+    pub added: RGB8,
+
+    /// A field is free although it should be occupied.
+    pub missing: RGB8,
+
+    /// The whole board pulses in this color when a player has won.
+    pub win: RGB8,
+
+    /// The whole board pulses in this color when the game ended in a draw.
+    pub draw: RGB8,
+}
+
+// Only the selected theme is used.
+#[allow(dead_code)]
+impl ColorTheme {
+    pub const DEFAULT: ColorTheme = ColorTheme {
+        players: [RGB8 { r: 0, g: 0, b: 255 }, RGB8 { r: 0, g: 255, b: 0 }],
+        players_lost: [RGB8 { r: 0, g: 0, b: 59 }, RGB8 { r: 0, g: 59, b: 0 }],
+        added: RGB8 { r: 167, g: 0, b: 0 },
+        missing: RGB8 {
+            r: 167,
+            g: 167,
+            b: 0,
+        },
+        win: RGB8 { r: 0, g: 255, b: 0 },
+        draw: RGB8 {
+            r: 255,
+            g: 191,
+            b: 0,
+        },
+    };
+
+    /// Like the default, but the errors are shown at full brightness
+    /// and the pieces of the loser are turned off.
+    pub const HIGH_CONTRAST: ColorTheme = ColorTheme {
+        players: [RGB8 { r: 0, g: 0, b: 255 }, RGB8 { r: 0, g: 255, b: 0 }],
+        players_lost: [RGB8 { r: 0, g: 0, b: 0 }, RGB8 { r: 0, g: 0, b: 0 }],
+        added: RGB8 { r: 255, g: 0, b: 0 },
+        missing: RGB8 {
+            r: 255,
+            g: 255,
+            b: 0,
+        },
+        win: RGB8 { r: 0, g: 255, b: 0 },
+        draw: RGB8 {
+            r: 255,
+            g: 191,
+            b: 0,
+        },
+    };
+
+    /// Avoids mixing red and green, so it can be used with red-green color blindness.
+    pub const COLORBLIND: ColorTheme = ColorTheme {
+        players: [
+            RGB8 { r: 0, g: 0, b: 255 },
+            RGB8 {
+                r: 255,
+                g: 194,
+                b: 0,
+            },
+        ],
+        players_lost: [RGB8 { r: 0, g: 0, b: 59 }, RGB8 { r: 59, g: 43, b: 0 }],
+        added: RGB8 {
+            r: 167,
+            g: 0,
+            b: 167,
+        },
+        missing: RGB8 {
+            r: 167,
+            g: 167,
+            b: 167,
+        },
+        win: RGB8 {
+            r: 255,
+            g: 255,
+            b: 255,
+        },
+        draw: RGB8 {
+            r: 255,
+            g: 194,
+            b: 0,
+        },
+    };
+}
@@ -1,32 +1,115 @@
 use anyhow::Result;
 use board::Board;
+#[cfg(feature = "buzzer")]
+use buzzer::Buzzer;
+use color_correction::ColorCorrection;
+use color_theme::ColorTheme;
 use esp_idf_hal::gpio::AnyIOPin;
 use esp_idf_hal::peripherals::Peripherals;
 use esp_idf_sys::xTaskCreatePinnedToCore;
 use log::*;
+use mapping::SquareMapping;
 use std::cell::RefCell;
+use std::f32::consts::PI;
 use std::ffi::{c_void, CString};
 use std::sync::Mutex;
 use std::thread::sleep;
-use std::time::Duration;
-use tic_tac_toe::TicTacToe;
+use std::time::{Duration, Instant};
+use tic_tac_toe::{TicTacToe, TickOutcome};
 use wifi::{wifi_loop_receiver, WifiParams, WIFI_PARAMS};
 use ws2812_esp32_rmt_driver::Ws2812Esp32Rmt;
 
 mod bitboard;
 mod board;
+#[cfg(feature = "buzzer")]
+mod buzzer;
+mod color_correction;
+mod color_theme;
+mod mapping;
 mod tic_tac_toe;
 mod wifi;
 
 const FIELD_SIZE: usize = 3;
 
+/// Change this if the PCB is routed rotated or mirrored.
+const SQUARE_MAPPING: SquareMapping = SquareMapping {
+    rotation: mapping::Rotation::None,
+    mirror: false,
+};
+
+/// How many ticks a field has to be stable before a change is accepted.
+const BOARD_DEBOUNCE_TICKS: u8 = 3;
+
+/// The board gets scanned with the fast interval right after any change
+/// and slows down once nothing changed for SLOW_TICK_AFTER.
+/// This reduces the load while e.g. nobody plays.
+const FAST_TICK_INTERVAL: Duration = Duration::from_millis(50);
+const SLOW_TICK_INTERVAL: Duration = Duration::from_millis(500);
+const SLOW_TICK_AFTER: Duration = Duration::from_secs(10);
+
+/// The colors used to show the game.
+const COLOR_THEME: ColorTheme = ColorTheme::DEFAULT;
+
+/// Gamma correction of the LEDs. 1.0 disables it.
+const LED_GAMMA: f32 = 2.2;
+
+/// Overall brightness of the LEDs from 0.0 to 1.0.
+const LED_BRIGHTNESS: f32 = 1.0;
+
+/// Correction factors for red, green and blue.
+/// E.g. lower the blue one if blue looks too bright compared to the other colors.
+const LED_CHANNEL_CORRECTION: [f32; 3] = [1.0, 1.0, 1.0];
+
+/// How long the whole board pulses when a game is over.
+const GAME_OVER_ANIMATION: Duration = Duration::from_secs(3);
+
+/// Duration of a single pulse of the game over animation.
+const GAME_OVER_PULSE: Duration = Duration::from_secs(1);
+
+/// Sweep all colors over the LEDs on startup to spot dead LEDs.
+const LED_SELF_TEST: bool = true;
+
 struct AppParams<'a, const N: usize> {
     board: Board<'a, N>,
     led_pin: AnyIOPin,
     channel: esp_idf_hal::rmt::CHANNEL0, // For now only channel0 - don't know how to type this to support any channel...
+    #[cfg(feature = "buzzer")]
+    buzzer: Buzzer<'a>,
 }
 static APP_PARAMS: Mutex<RefCell<Option<AppParams<FIELD_SIZE>>>> = Mutex::new(RefCell::new(None));
 
+/// Lights up all LEDs in red, green and blue one after another and clears them afterwards.
+/// This way dead LEDs or a flaky data line can be spotted easily.
+/// It takes less than a second.
+fn led_self_test(ws2812: &mut Ws2812Esp32Rmt) {
+    for color in [
+        smart_leds::RGB8 { r: 255, g: 0, b: 0 },
+        smart_leds::RGB8 { r: 0, g: 255, b: 0 },
+        smart_leds::RGB8 { r: 0, g: 0, b: 255 },
+    ] {
+        ws2812
+            .write_nocopy([color; FIELD_SIZE * FIELD_SIZE])
+            .unwrap();
+        sleep(Duration::from_millis(300));
+    }
+
+    ws2812
+        .write_nocopy([smart_leds::RGB8 { r: 0, g: 0, b: 0 }; FIELD_SIZE * FIELD_SIZE])
+        .unwrap();
+}
+
+/// Fades the color in and out once per GAME_OVER_PULSE.
+fn pulse(color: smart_leds::RGB8, elapsed: Duration) -> smart_leds::RGB8 {
+    let phase = elapsed.as_secs_f32() / GAME_OVER_PULSE.as_secs_f32() * 2.0 * PI;
+    let factor = (1.0 - phase.cos()) / 2.0;
+
+    smart_leds::RGB8 {
+        r: (color.r as f32 * factor) as u8,
+        g: (color.g as f32 * factor) as u8,
+        b: (color.b as f32 * factor) as u8,
+    }
+}
+
 extern "C" fn app_loop_receiver(_: *mut c_void) {
     // Fetch the app params and remove it afterwards.
     let app_mu = APP_PARAMS.lock().unwrap();
@@ -36,30 +119,66 @@ extern "C" fn app_loop_receiver(_: *mut c_void) {
     let app = app_mu_ref.expect("app params not");
     let mut ws2812 = Ws2812Esp32Rmt::new(app.channel, app.led_pin).unwrap();
     let mut board = app.board;
+    #[cfg(feature = "buzzer")]
+    let mut buzzer = app.buzzer;
 
+    if LED_SELF_TEST {
+        led_self_test(&mut ws2812);
+    }
+
+    let color_correction = ColorCorrection::new(LED_GAMMA, LED_BRIGHTNESS, LED_CHANNEL_CORRECTION);
     let mut tic_tac_toe: TicTacToe<FIELD_SIZE> = TicTacToe::new();
+    let mut last_change = Instant::now();
+
+    // Set while the game over animation is running.
+    let mut game_over_at: Option<Instant> = None;
 
     loop {
-        board.tick();
+        if board.tick() {
+            last_change = Instant::now();
+        }
         let game = tic_tac_toe.tick(board.bitboard());
 
+        if game.outcome != TickOutcome::Nothing {
+            info!("Tick outcome: {:?}", game.outcome);
+        }
+
+        match game.outcome {
+            TickOutcome::GameOver => game_over_at = Some(Instant::now()),
+            // Taking back the last move continues the game.
+            TickOutcome::Undone => game_over_at = None,
+            _ => (),
+        }
+
+        // Clearing the board starts a new game.
+        if board.bitboard() == 0 {
+            game_over_at = None;
+        }
+
+        #[cfg(feature = "buzzer")]
+        match game.outcome {
+            TickOutcome::Moved | TickOutcome::GameOver => buzzer.beep_ok().unwrap(),
+            TickOutcome::Rejected => buzzer.beep_error().unwrap(),
+            _ => (),
+        }
+
         // make black
-        let mut pixels = [smart_leds::RGB { r: 0, g: 0, b: 0 }; 9];
+        let mut pixels = [smart_leds::RGB8 { r: 0, g: 0, b: 0 }; FIELD_SIZE * FIELD_SIZE];
 
-        println!("Board   {:032b}", board.bitboard());
-        println!("Player1 {:032b}", game.board.players[0]);
-        println!("Player2 {:032b}", game.board.players[1]);
+        debug!("Board   {:064b}", board.bitboard());
+        debug!("Player1 {:064b}", game.board.players[0]);
+        debug!("Player2 {:064b}", game.board.players[1]);
 
         for (row, columns) in board.field.iter().enumerate() {
             for (column, value) in columns.iter().enumerate() {
-                let mut pixel = row * board.size() + column;
-                if row % 2 == 0 {
-                    pixel = row * board.size() + (board.size() - column - 1);
+                let (led_row, led_column) =
+                    board.mapping().to_physical((row, column), board.size());
+                let mut pixel = led_row * board.size() + led_column;
+                if led_row % 2 == 0 {
+                    pixel = led_row * board.size() + (board.size() - led_column - 1);
                 }
 
-                let pos = (board.size() - row - 1) * board.size()
-                    + (board.size() - column - 1)
-                    + (board.size() - row - 1) * (8 - board.size()); // Padding to the bigger u32 chess board
+                let pos = bitboard::position(row, column, board.size());
                 let player1: bool = bitboard::get(game.board.players[0], pos);
                 let player2: bool = bitboard::get(game.board.players[1], pos);
 
@@ -70,38 +189,53 @@ extern "C" fn app_loop_receiver(_: *mut c_void) {
 
                 if player1 {
                     if game.board.winner == Some(1) {
-                        pixels[pixel] = smart_leds::RGB { r: 0, g: 0, b: 10 }
+                        pixels[pixel] = COLOR_THEME.players_lost[0];
                     } else {
-                        pixels[pixel] = smart_leds::RGB { r: 0, g: 0, b: 255 }
+                        pixels[pixel] = COLOR_THEME.players[0];
                     }
                 } else if player2 {
                     if game.board.winner == Some(0) {
-                        pixels[pixel] = smart_leds::RGB { r: 0, g: 10, b: 0 }
+                        pixels[pixel] = COLOR_THEME.players_lost[1];
                     } else {
-                        pixels[pixel] = smart_leds::RGB { r: 0, g: 255, b: 0 }
+                        pixels[pixel] = COLOR_THEME.players[1];
                     }
                 } else if *value {
                     // Something is wrong because the field should not be occupied.
                     // Can happen if the program starts while there are still parts on the
                     // board.
-                    pixels[pixel] = smart_leds::RGB { r: 100, g: 0, b: 0 }
+                    pixels[pixel] = COLOR_THEME.added;
                 }
 
                 if !*value && (player1 || player2) {
                     // Something is wrong, because the field should be occupied.
                     // This may happen if a part got removed which should not be done.
-                    pixels[pixel] = smart_leds::RGB {
-                        r: 100,
-                        g: 100,
-                        b: 0,
-                    }
+                    pixels[pixel] = COLOR_THEME.missing;
                 }
             }
         }
 
-        ws2812.write_nocopy(pixels).unwrap();
+        if let Some(started) = game_over_at {
+            if started.elapsed() < GAME_OVER_ANIMATION {
+                let color = if game.board.winner.is_some() {
+                    COLOR_THEME.win
+                } else {
+                    COLOR_THEME.draw
+                };
+                pixels = [pulse(color, started.elapsed()); FIELD_SIZE * FIELD_SIZE];
+            } else {
+                game_over_at = None;
+            }
+        }
 
-        sleep(Duration::from_millis(100));
+        ws2812
+            .write_nocopy(pixels.map(|pixel| color_correction.scale(pixel)))
+            .unwrap();
+
+        if last_change.elapsed() < SLOW_TICK_AFTER {
+            sleep(FAST_TICK_INTERVAL);
+        } else {
+            sleep(SLOW_TICK_INTERVAL);
+        }
 
         // Uncomment for debugging
         // sleep(Duration::from_secs(1));
@@ -132,6 +266,8 @@ fn main() -> Result<()> {
             AnyIOPin::from(peripherals.pins.gpio33),
             AnyIOPin::from(peripherals.pins.gpio25),
         ],
+        SQUARE_MAPPING,
+        BOARD_DEBOUNCE_TICKS,
     );
     board.setup();
 
@@ -147,6 +283,13 @@ fn main() -> Result<()> {
         board: board,
         led_pin: AnyIOPin::from(peripherals.pins.gpio23),
         channel: peripherals.rmt.channel0,
+        #[cfg(feature = "buzzer")]
+        buzzer: Buzzer::new(
+            peripherals.ledc.timer0,
+            peripherals.ledc.channel0,
+            peripherals.pins.gpio22,
+        )
+        .unwrap(),
     }));
     drop(app_params);
 
@@ -0,0 +1,60 @@
+/// Clockwise rotation of the physical board in steps of 90°.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)] // Which variant is used depends on the hardware.
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+/// Describes how the hardware (sensor matrix and LEDs) is routed compared
+/// to the logical playing field.
+/// The default is the identity mapping, which resembles the original wiring.
+///
+/// As the sensors and the LEDs sit on the same PCB, the same mapping is used
+/// for both of them.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SquareMapping {
+    /// Rotation which is applied first.
+    pub rotation: Rotation,
+
+    /// Mirror the columns after rotating.
+    pub mirror: bool,
+}
+
+/// A (row, column) pair.
+pub type Position = (usize, usize);
+
+impl SquareMapping {
+    /// Maps a logical position on a NxN field to the physical position in the hardware.
+    pub fn to_physical(&self, (row, column): Position, n: usize) -> Position {
+        let (row, column) = match self.rotation {
+            Rotation::None => (row, column),
+            Rotation::Cw90 => (column, n - row - 1),
+            Rotation::Cw180 => (n - row - 1, n - column - 1),
+            Rotation::Cw270 => (n - column - 1, row),
+        };
+
+        if self.mirror {
+            (row, n - column - 1)
+        } else {
+            (row, column)
+        }
+    }
+
+    /// Maps a physical position in the hardware back to the logical position on a NxN field.
+    /// This is the inverse of [`SquareMapping::to_physical`].
+    pub fn to_logical(&self, (row, column): Position, n: usize) -> Position {
+        // Undo the mirror first as it got applied last.
+        let column = if self.mirror { n - column - 1 } else { column };
+
+        match self.rotation {
+            Rotation::None => (row, column),
+            Rotation::Cw90 => (n - column - 1, row),
+            Rotation::Cw180 => (n - row - 1, n - column - 1),
+            Rotation::Cw270 => (column, n - row - 1),
+        }
+    }
+}
@@ -5,21 +5,6 @@ use crate::bitboard::*;
 /// Represents board coordinates.
 type PlayingPosition = (u8, u8);
 
-const BOARD_MASK: u32 = 0b00000000_00000000_00000000_00000000_00000000_00000111_00000111_00000111;
-const WINNING_MASKS: [u32; 8] = [
-    // rows
-    0b00000000_00000000_00000000_00000000_00000000_00000111_00000000_00000000,
-    0b00000000_00000000_00000000_00000000_00000000_00000000_00000111_00000000,
-    0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_00000111,
-    // columns
-    0b00000000_00000000_00000000_00000000_00000000_00000100_00000100_00000100,
-    0b00000000_00000000_00000000_00000000_00000000_00000010_00000010_00000010,
-    0b00000000_00000000_00000000_00000000_00000000_00000001_00000001_00000001,
-    // diagonals
-    0b00000000_00000000_00000000_00000000_00000000_00000100_00000010_00000001,
-    0b00000000_00000000_00000000_00000000_00000000_00000001_00000010_00000100,
-];
-
 /// Represents a move that can be performed by the computer.
 #[derive(Clone, Copy)]
 struct Move {
@@ -49,59 +34,89 @@ pub struct HistoryEntry {
     // And to manipulate the state by using bit operations
     //
     /// The pieces of each player respectively.
-    pub players: [u32; 2],
+    pub players: [u64; 2],
 
     /// If there is a winner its index is saved here.
     pub winner: Option<usize>,
 }
 
 impl HistoryEntry {
-    fn get_occupied(self) -> u32 {
+    fn get_occupied(self) -> u64 {
         self.players[0] | self.players[1]
     }
 
-    fn get_free(self) -> u32 {
-        return !self.get_occupied() & BOARD_MASK;
+    fn get_free(self, board_mask: u64) -> u64 {
+        return !self.get_occupied() & board_mask;
     }
 }
 
+/// Describes what happened during a single tick.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TickOutcome {
+    /// Nothing changed.
+    Nothing,
+
+    /// A new piece got placed.
+    Moved,
+
+    /// The last move got taken back.
+    Undone,
+
+    /// A new piece got placed and it ended the game.
+    /// Either a player has won or the board is full, which is a draw.
+    /// It is reported only once per game.
+    GameOver,
+
+    /// The board got changed in a way which is not allowed.
+    /// E.g. more than one piece got placed or a piece got moved.
+    /// Only removing pieces is not reported, so clearing the board is silent,
+    /// although the missing pieces are still shown.
+    /// It is only reported once for each change of the board.
+    Rejected,
+}
+
 pub struct GameState {
     pub board: HistoryEntry,
     pub _player: usize,
+    pub outcome: TickOutcome,
 }
 
+/// TicTacToe on a NxN field.
+/// N can be at most `BITBOARD_WIDTH` as the field has to fit into a u64 bitboard.
 pub(crate) struct TicTacToe<const N: usize> {
-    // TicTacToe has a fixed count of possible history entries.
-    // So no need for a dynamic data structure.
-    //
     /// Contains the full game history.
-    /// It should contain Some state up to the current index.
-    /// The first element should always contain the initial state.
-    history: [Option<HistoryEntry>; 10],
+    /// The first element always contains the initial state
+    /// and the last one the current state.
+    /// It can hold at most N * N + 1 entries.
+    history: Vec<HistoryEntry>,
 
-    /// The current index in the history
-    current_index: usize,
+    /// All fields of the NxN board.
+    board_mask: u64,
+
+    /// All rows, columns and both diagonals of the NxN board.
+    winning_masks: Vec<u64>,
+
+    /// The occupied fields passed to the last tick.
+    /// Used to report a rejected change only once.
+    last_input: u64,
 }
 
 impl<const N: usize> Default for TicTacToe<N> {
     fn default() -> Self {
+        // Each row of the bitboard has only BITBOARD_WIDTH bits.
+        assert!(N <= BITBOARD_WIDTH);
+
+        let mut history = Vec::with_capacity(N * N + 1);
+        history.push(HistoryEntry {
+            players: [0, 0],
+            winner: None,
+        });
+
         Self {
-            history: [
-                Some(HistoryEntry {
-                    players: [0, 0],
-                    winner: None,
-                }),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-            ],
-            current_index: 0,
+            history,
+            board_mask: Self::generate_board_mask(),
+            winning_masks: Self::generate_winning_masks(),
+            last_input: 0,
         }
     }
 }
@@ -111,41 +126,83 @@ impl<const N: usize> TicTacToe<N> {
         TicTacToe::default()
     }
 
+    fn generate_board_mask() -> u64 {
+        let mut mask = 0;
+        for row in 0..N {
+            for column in 0..N {
+                mask = set_bit(mask, position(row, column, N));
+            }
+        }
+        mask
+    }
+
+    fn generate_winning_masks() -> Vec<u64> {
+        let mut masks = Vec::with_capacity(2 * N + 2);
+
+        // rows
+        for row in 0..N {
+            masks.push((0..N).fold(0, |mask, column| set_bit(mask, position(row, column, N))));
+        }
+
+        // columns
+        for column in 0..N {
+            masks.push((0..N).fold(0, |mask, row| set_bit(mask, position(row, column, N))));
+        }
+
+        // diagonals
+        masks.push((0..N).fold(0, |mask, i| set_bit(mask, position(i, i, N))));
+        masks.push((0..N).fold(0, |mask, i| set_bit(mask, position(i, N - i - 1, N))));
+
+        masks
+    }
+
+    /// The index of the current state in the history.
+    fn current_index(&self) -> usize {
+        self.history.len() - 1
+    }
+
     fn current(&self) -> HistoryEntry {
-        self.history[self.current_index].expect("index not in the history")
+        *self.history.last().expect("history is empty")
     }
 
     fn last(&self) -> Option<HistoryEntry> {
-        if self.current_index == 0 {
+        if self.current_index() == 0 {
             return None;
         }
 
-        self.history[self.current_index - 1]
+        Some(self.history[self.current_index() - 1])
     }
 
     fn current_player(&self) -> usize {
-        self.current_index % 2
+        self.current_index() % 2
     }
 
     fn push(&mut self, new_state: HistoryEntry) {
-        self.current_index += 1;
-        self.history[self.current_index] = Some(new_state);
+        self.history.push(new_state);
     }
 
     fn pull(&mut self) -> HistoryEntry {
-        self.history[self.current_index] = None;
-        self.current_index -= 1;
+        self.history.pop();
         return self.current();
     }
 
-    pub fn tick(&mut self, now_occupied: u32) -> GameState {
+    pub fn tick(&mut self, now_occupied: u64) -> GameState {
+        // Only the NxN field is used for the game.
+        let now_occupied = now_occupied & self.board_mask;
         let state = self.current();
 
+        let rejected = if now_occupied != self.last_input {
+            TickOutcome::Rejected
+        } else {
+            TickOutcome::Nothing
+        };
+        self.last_input = now_occupied;
+
         let last_occupied = state.get_occupied();
         let current_player = self.current_player();
 
         // If the new board is empty - reset the game.
-        if now_occupied == 0 && self.current_index != 0 {
+        if now_occupied == 0 && self.current_index() != 0 {
             info!("reset game");
             *self = TicTacToe::default()
         }
@@ -155,6 +212,7 @@ impl<const N: usize> TicTacToe<N> {
             return GameState {
                 board: state,
                 _player: current_player,
+                outcome: TickOutcome::Nothing,
             };
         }
 
@@ -165,9 +223,17 @@ impl<const N: usize> TicTacToe<N> {
                     if last.get_occupied() != now_occupied {
                         // The new state is not the same like the last one.
                         // Do notheing
+                        // Only removing pieces is not rejected, as it happens
+                        // e.g. while the board gets cleared to start a new game.
+                        let only_removed = now_occupied & !last_occupied == 0;
                         return GameState {
                             board: state,
                             _player: current_player,
+                            outcome: if only_removed {
+                                TickOutcome::Nothing
+                            } else {
+                                rejected
+                            },
                         };
                     }
 
@@ -176,17 +242,20 @@ impl<const N: usize> TicTacToe<N> {
                     return GameState {
                         board: previous,
                         _player: self.current_player(),
+                        outcome: TickOutcome::Undone,
                     };
                 }
                 None => GameState {
                     board: state,
                     _player: current_player,
+                    outcome: TickOutcome::Nothing,
                 },
             };
         } else if last_occupied == now_occupied {
             return GameState {
                 board: state,
                 _player: current_player,
+                outcome: TickOutcome::Nothing,
             };
         }
 
@@ -199,6 +268,7 @@ impl<const N: usize> TicTacToe<N> {
             return GameState {
                 board: state,
                 _player: current_player,
+                outcome: rejected,
             };
         }
 
@@ -209,9 +279,16 @@ impl<const N: usize> TicTacToe<N> {
         self.calculate_win(&mut new_state);
         self.push(new_state);
 
+        let outcome = if new_state.winner.is_some() || new_state.get_occupied() == self.board_mask {
+            TickOutcome::GameOver
+        } else {
+            TickOutcome::Moved
+        };
+
         return GameState {
             board: new_state,
             _player: self.current_player(),
+            outcome,
         };
     }
 
@@ -219,7 +296,7 @@ impl<const N: usize> TicTacToe<N> {
     /// Sets the respective player as winner if needed.
     fn calculate_win(&self, state: &mut HistoryEntry) {
         for (player_index, player) in state.players.iter().enumerate() {
-            for mask in WINNING_MASKS.iter() {
+            for mask in self.winning_masks.iter() {
                 if *player & *mask == *mask {
                     state.winner = Some(player_index);
                     return;
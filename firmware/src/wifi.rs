@@ -1,4 +1,4 @@
-use anyhow::{Ok, Result};
+use anyhow::{anyhow, Result};
 use esp_idf_hal::{io::Write, modem};
 use esp_idf_svc::wifi;
 use esp_idf_svc::{
@@ -7,11 +7,13 @@ use esp_idf_svc::{
         server::{self, EspHttpServer},
         Method,
     },
+    mdns::EspMdns,
     nvs::EspDefaultNvsPartition,
     wifi::{BlockingWifi, EspWifi},
 };
 use log::*;
 use std::thread::sleep;
+use std::time::Instant;
 use std::{cell::RefCell, os::raw::c_void, sync::Mutex, time::Duration};
 
 pub(crate) struct WifiParams {
@@ -19,43 +21,92 @@ pub(crate) struct WifiParams {
 }
 pub(crate) static WIFI_PARAMS: Mutex<RefCell<Option<WifiParams>>> = Mutex::new(RefCell::new(None));
 
-const WIFI_SSID: &str = "Freifunk";
-const WIFI_PASS: &str = "";
+/// All known networks as (ssid, password).
+/// They are tried one after another until one of them connects.
+/// An empty password means an open network.
+const WIFI_NETWORKS: &[(&str, &str)] = &[("Freifunk", "")];
+
+/// The board is reachable as http://<MDNS_HOSTNAME>.local
+/// Change it if more than one board is used in the same network.
+const MDNS_HOSTNAME: &str = "e-chess";
+
+/// How often the signal strength gets logged.
+/// Helps to find a good position for the board.
+const RSSI_INTERVAL: Duration = Duration::from_secs(10);
 
 fn connect_wifi(wifi: &mut BlockingWifi<EspWifi<'static>>) -> anyhow::Result<()> {
-    info!("Connect to SSID: {}", WIFI_SSID);
     sleep(Duration::from_secs(1));
 
+    let mut last_error = anyhow!("no wifi network configured");
+    for (ssid, password) in WIFI_NETWORKS {
+        if let Err(err) = try_connect(wifi, ssid, password) {
+            warn!("Could not connect to SSID {}: {}", ssid, err);
+            // Just to be sure the next network starts from a clean state.
+            wifi.disconnect().ok();
+            last_error = err;
+            continue;
+        }
+
+        let ip_info = wifi.wifi().sta_netif().get_ip_info()?;
+        info!(
+            "IP: \n{}\n{}\n{:?}\n{:?}",
+            ip_info.ip, ip_info.subnet, ip_info.dns, ip_info.secondary_dns
+        );
+
+        return Ok(());
+    }
+
+    Err(last_error)
+}
+
+fn try_connect(
+    wifi: &mut BlockingWifi<EspWifi<'static>>,
+    ssid: &str,
+    password: &str,
+) -> anyhow::Result<()> {
+    info!("Connect to SSID: {}", ssid);
+
+    let auth_method = if password.is_empty() {
+        wifi::AuthMethod::None
+    } else {
+        wifi::AuthMethod::WPA2Personal
+    };
+
     let wifi_configuration: wifi::Configuration =
         wifi::Configuration::Client(wifi::ClientConfiguration {
-            ssid: WIFI_SSID.try_into().expect("ssid could not be read"),
+            ssid: ssid.try_into().expect("ssid could not be read"),
             bssid: None,
-            auth_method: esp_idf_svc::wifi::AuthMethod::None,
-            password: WIFI_PASS.try_into().expect("password could not be read"),
+            auth_method,
+            password: password.try_into().expect("password could not be read"),
             channel: None,
             ..Default::default()
         });
 
     wifi.set_configuration(&wifi_configuration)?;
 
-    wifi.start()?;
-    info!("Wifi started");
+    if !wifi.is_started()? {
+        wifi.start()?;
+        info!("Wifi started");
+    }
 
-    wifi.connect().expect("could not connect");
+    wifi.connect()?;
     info!("Wifi connected");
 
     wifi.wait_netif_up()?;
     info!("Wifi netif up");
 
-    let ip_info = wifi.wifi().sta_netif().get_ip_info()?;
-    info!(
-        "IP: \n{}\n{}\n{:?}\n{:?}",
-        ip_info.ip, ip_info.subnet, ip_info.dns, ip_info.secondary_dns
-    );
-
     return Ok(());
 }
 
+fn start_mdns() -> Result<EspMdns> {
+    let mut mdns = EspMdns::take()?;
+    mdns.set_hostname(MDNS_HOSTNAME)?;
+    mdns.set_instance_name("E-Chess")?;
+    mdns.add_service(None, "_http", "_tcp", 80, &[])?;
+
+    Ok(mdns)
+}
+
 fn index_html() -> String {
     format!(
         r#"
@@ -99,6 +150,20 @@ fn wifi_loop() -> Result<()> {
 
     info!("WIFI connection done");
 
+    // Advertise the board, so it can be found without knowing its IP.
+    // mDNS is only a convenience, so the server is still reachable by its IP if it fails.
+    // The responder stops when it is dropped, so it has to live as long as the server.
+    let _mdns = match start_mdns() {
+        Ok(mdns) => {
+            info!("mDNS hostname: {}.local", MDNS_HOSTNAME);
+            Some(mdns)
+        }
+        Err(err) => {
+            warn!("Could not start mDNS: {}", err);
+            None
+        }
+    };
+
     // Set the HTTP server
     let mut server = EspHttpServer::new(&server::Configuration::default()).unwrap();
     // http://<sta ip>/ handler
@@ -107,11 +172,21 @@ fn wifi_loop() -> Result<()> {
             let html = index_html();
             let mut response = request.into_ok_response()?;
             response.write_all(html.as_bytes())?;
-            Ok(())
+            anyhow::Ok(())
         })
         .unwrap();
 
+    let mut last_rssi_check = Instant::now();
     loop {
+        if last_rssi_check.elapsed() >= RSSI_INTERVAL {
+            last_rssi_check = Instant::now();
+
+            match wifi.wifi_mut().driver_mut().get_ap_info() {
+                Ok(ap_info) => info!("Wifi RSSI: {} dBm", ap_info.signal_strength),
+                Err(err) => warn!("Could not read the wifi RSSI: {}", err),
+            }
+        }
+
         sleep(Duration::from_millis(100));
     }
 }